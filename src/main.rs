@@ -1,3 +1,12 @@
+use std::collections::HashMap;
+
+mod stakes;
+mod validation;
+mod voting_power;
+mod withdrawals;
+
+use validation::Validator;
+
 // What can be improved in the following code?
 fn withdraw_funds(balance: u64, amount_to_withdraw: u64) -> Result<u64, String> {
     if amount_to_withdraw > balance {
@@ -27,13 +36,39 @@ pub struct Balance(u64);
 pub struct Amount(u64);
 
 impl Balance {
-    fn get(&self) -> u64 {
+    pub(crate) fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Constructs a `Balance`, rejecting `value` if `validator` is given
+    /// and `value` fails its check.
+    pub fn try_new(value: u64, validator: Option<&dyn Validator<u64>>) -> Result<Self, String> {
+        if let Some(validator) = validator {
+            validator.check(&value)?;
+        }
+        Ok(Self(value))
+    }
+
+    pub(crate) fn get(&self) -> u64 {
         self.0
     }
 }
 
 impl Amount {
-    fn get(&self) -> u64 {
+    pub(crate) fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Constructs an `Amount`, rejecting `value` if `validator` is given
+    /// and `value` fails its check.
+    pub fn try_new(value: u64, validator: Option<&dyn Validator<u64>>) -> Result<Self, String> {
+        if let Some(validator) = validator {
+            validator.check(&value)?;
+        }
+        Ok(Self(value))
+    }
+
+    pub(crate) fn get(&self) -> u64 {
         self.0
     }
 }
@@ -48,10 +83,295 @@ fn withdraw_funds_even_better(
     Ok(Balance(balance.get() - amount_to_withdraw.get()))
 }
 
+// `withdraw_funds_even_better` only ever applies one operation at a time. A
+// `TransactionBuilder` accumulates a whole batch of debits and credits and
+// only commits them once the entire batch has been checked, giving callers
+// all-or-nothing semantics instead of partially-applied transfers.
+
+type AccountId = String;
+
+#[derive(Debug)]
+pub enum BuildError {
+    UnknownAccount {
+        account_id: AccountId,
+    },
+    InsufficientFunds {
+        account_id: AccountId,
+        needed: u64,
+        available: u64,
+    },
+}
+
+enum Operation {
+    Withdrawal { account_id: AccountId, amount: Amount },
+    Deposit { account_id: AccountId, amount: Amount },
+}
+
+/// Stages a batch of withdrawals and deposits against a set of named
+/// accounts, then replays the whole batch against a working copy of the
+/// balances so it can be committed atomically.
+pub struct TransactionBuilder {
+    accounts: HashMap<AccountId, Balance>,
+    operations: Vec<Operation>,
+}
+
+impl TransactionBuilder {
+    pub fn new(accounts: HashMap<AccountId, Balance>) -> Self {
+        Self {
+            accounts,
+            operations: Vec::new(),
+        }
+    }
+
+    pub fn add_withdrawal(mut self, account_id: impl Into<AccountId>, amount: Amount) -> Self {
+        self.operations.push(Operation::Withdrawal {
+            account_id: account_id.into(),
+            amount,
+        });
+        self
+    }
+
+    pub fn add_deposit(mut self, account_id: impl Into<AccountId>, amount: Amount) -> Self {
+        self.operations.push(Operation::Deposit {
+            account_id: account_id.into(),
+            amount,
+        });
+        self
+    }
+
+    /// Replays every staged operation against a working copy of the
+    /// balances, failing the whole batch (and leaving the original
+    /// balances untouched) if any intermediate step would underflow.
+    pub fn build(self) -> Result<HashMap<AccountId, Balance>, BuildError> {
+        let mut working: HashMap<AccountId, u64> = self
+            .accounts
+            .iter()
+            .map(|(account_id, balance)| (account_id.clone(), balance.get()))
+            .collect();
+
+        for operation in &self.operations {
+            match operation {
+                Operation::Withdrawal { account_id, amount } => {
+                    let available =
+                        working
+                            .get_mut(account_id)
+                            .ok_or_else(|| BuildError::UnknownAccount {
+                                account_id: account_id.clone(),
+                            })?;
+                    let needed = amount.get();
+                    if needed > *available {
+                        return Err(BuildError::InsufficientFunds {
+                            account_id: account_id.clone(),
+                            needed,
+                            available: *available,
+                        });
+                    }
+                    *available -= needed;
+                }
+                Operation::Deposit { account_id, amount } => {
+                    let available =
+                        working
+                            .get_mut(account_id)
+                            .ok_or_else(|| BuildError::UnknownAccount {
+                                account_id: account_id.clone(),
+                            })?;
+                    *available += amount.get();
+                }
+            }
+        }
+
+        Ok(working
+            .into_iter()
+            .map(|(account_id, balance)| (account_id, Balance(balance)))
+            .collect())
+    }
+}
+
+// `withdraw_funds_even_better` always runs every check it knows about. Real
+// callers sometimes only want the cheap, always-valid subset of checks (for
+// example to pre-filter a batch before it is known which slot it will land
+// in), so `verify_transfer`/`execute_transfer` split verification out from
+// mutation and make the set of checks that run configurable.
+
+/// The transfer being evaluated, together with the sender's current balance
+/// and the slot at which the sender becomes eligible to send.
+pub struct Transfer {
+    pub sender_balance: Balance,
+    pub amount: Amount,
+    pub sender_eligible_at_slot: u64,
+}
+
+/// The state a transfer is verified against, kept separate from the
+/// `Transfer` itself since it is shared across a whole batch.
+pub struct TransferState {
+    pub current_slot: u64,
+}
+
+/// Controls which checks `verify_transfer` runs.
+pub struct TransferOpts {
+    /// When `true`, only the structural invariants that hold regardless of
+    /// `TransferState` are checked. No such checks exist yet, so
+    /// `verify_transfer` currently passes unconditionally in this mode;
+    /// balance adequacy, dust, and sender eligibility all still run when
+    /// this is `false`.
+    pub time_independent_only: bool,
+}
+
+#[derive(Debug)]
+pub enum TransferError {
+    Underflow { needed: u64, available: u64 },
+    Dust { amount: u64 },
+    SenderIneligible { eligible_at_slot: u64, current_slot: u64 },
+}
+
+impl TransferError {
+    /// Tags this error with the index of the transfer that produced it, so
+    /// a batch processor can report which entry failed.
+    pub fn into_with_index(self, index: usize) -> IndexedTransferError {
+        IndexedTransferError { index, error: self }
+    }
+}
+
+#[derive(Debug)]
+pub struct IndexedTransferError {
+    pub index: usize,
+    pub error: TransferError,
+}
+
+/// Checks `transfer` against `state`. Borrows both immutably: verification
+/// never mutates anything, it only decides whether `execute_transfer` is
+/// allowed to.
+pub fn verify_transfer(
+    state: &TransferState,
+    transfer: &Transfer,
+    opts: &TransferOpts,
+) -> Result<(), TransferError> {
+    if opts.time_independent_only {
+        return Ok(());
+    }
+
+    if transfer.amount.get() > transfer.sender_balance.get() {
+        return Err(TransferError::Underflow {
+            needed: transfer.amount.get(),
+            available: transfer.sender_balance.get(),
+        });
+    }
+    if transfer.amount.get() == 0 {
+        return Err(TransferError::Dust {
+            amount: transfer.amount.get(),
+        });
+    }
+    if state.current_slot < transfer.sender_eligible_at_slot {
+        return Err(TransferError::SenderIneligible {
+            eligible_at_slot: transfer.sender_eligible_at_slot,
+            current_slot: state.current_slot,
+        });
+    }
+    Ok(())
+}
+
+/// Verifies `transfer` against `state` and, if it passes, applies the debit
+/// and returns the sender's new `Balance`.
+pub fn execute_transfer(
+    state: &TransferState,
+    transfer: Transfer,
+    opts: &TransferOpts,
+) -> Result<Balance, TransferError> {
+    verify_transfer(state, &transfer, opts)?;
+    // `time_independent_only` skips the balance check, so guard the debit here.
+    let needed = transfer.amount.get();
+    let available = transfer.sender_balance.get();
+    let remaining = available
+        .checked_sub(needed)
+        .ok_or(TransferError::Underflow { needed, available })?;
+    Ok(Balance(remaining))
+}
+
 pub struct UpdateableConfigs {
     /// `None` means that the validator key existence could not be determined.
     /// `Some(None)` means that it was determined that the validator key does not exist.
-    pub validator_signer: Option<UpdateableValidatorSigner>,
+    pub validator_signer: Option<Option<UpdateableValidatorSigner>>,
+}
+
+/// A validator key that has been confirmed to exist, together with the
+/// epoch inputs needed to place it in its lifecycle.
+pub struct UpdateableValidatorSigner {
+    pub balance: Balance,
+    pub slashed: bool,
+    /// `None` if the validator has not yet been queued for activation.
+    pub activation_epoch: Option<u64>,
+    /// `None` if the validator has not initiated an exit.
+    pub exit_epoch: Option<u64>,
+    /// `None` until the validator's exit has been processed.
+    pub withdrawable_epoch: Option<u64>,
+}
+
+/// Where a validator sits in its activation/exit/withdrawal lifecycle,
+/// mirroring the status values exposed by validator-facing APIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidatorStatus {
+    PendingInitialized,
+    PendingQueued,
+    ActiveOngoing,
+    ActiveExiting,
+    ExitedSlashed,
+    ExitedUnslashed,
+    WithdrawalPossible,
+    WithdrawalDone,
+}
+
+impl UpdateableConfigs {
+    /// Resolves the current lifecycle status of this config's validator
+    /// from `current_epoch`, or `None` if the validator's existence could
+    /// not be determined or it was determined not to exist.
+    pub fn validator_status(&self, current_epoch: u64) -> Option<ValidatorStatus> {
+        let signer = self.validator_signer.as_ref()?.as_ref()?;
+
+        if let Some(withdrawable_epoch) = signer.withdrawable_epoch {
+            if current_epoch >= withdrawable_epoch {
+                return Some(if signer.balance.get() == 0 {
+                    ValidatorStatus::WithdrawalDone
+                } else {
+                    ValidatorStatus::WithdrawalPossible
+                });
+            }
+        }
+
+        if let Some(exit_epoch) = signer.exit_epoch {
+            if current_epoch >= exit_epoch {
+                return Some(if signer.slashed {
+                    ValidatorStatus::ExitedSlashed
+                } else {
+                    ValidatorStatus::ExitedUnslashed
+                });
+            }
+            return Some(ValidatorStatus::ActiveExiting);
+        }
+
+        match signer.activation_epoch {
+            Some(activation_epoch) if current_epoch >= activation_epoch => {
+                Some(ValidatorStatus::ActiveOngoing)
+            }
+            Some(_) => Some(ValidatorStatus::PendingQueued),
+            None => Some(ValidatorStatus::PendingInitialized),
+        }
+    }
+}
+
+/// Selects the configs whose validator is currently in one of `statuses`.
+pub fn filter_by_status<'a>(
+    configs: &'a [UpdateableConfigs],
+    current_epoch: u64,
+    statuses: &[ValidatorStatus],
+) -> Vec<&'a UpdateableConfigs> {
+    configs
+        .iter()
+        .filter(|config| {
+            config
+                .validator_status(current_epoch)
+                .is_some_and(|status| statuses.contains(&status))
+        })
+        .collect()
 }
 
 fn main() {}
@@ -0,0 +1,95 @@
+//! Extends the lone `Balance`/`withdraw_funds` pattern into a multi-account
+//! staking ledger keyed by `(validator, withdrawer)`, enforcing that a
+//! validator is only ever associated with one withdrawer.
+
+use std::collections::HashMap;
+
+use crate::{Amount, Balance};
+
+#[derive(Debug)]
+pub enum StakeError {
+    DifferentWithdrawer { validator: String },
+    UnknownPair { validator: String, withdrawer: String },
+    InsufficientStake { needed: u64, available: u64 },
+}
+
+/// Tracks staked `Balance` per `(validator, withdrawer)` pair.
+#[derive(Default)]
+pub struct Stakes {
+    balances: HashMap<(String, String), Balance>,
+    withdrawer_by_validator: HashMap<String, String>,
+}
+
+impl Stakes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails with `DifferentWithdrawer` if `validator` is already
+    /// registered under a withdrawer other than `withdrawer`.
+    pub fn check_validator_withdrawer(
+        &self,
+        validator: &str,
+        withdrawer: &str,
+    ) -> Result<(), StakeError> {
+        match self.withdrawer_by_validator.get(validator) {
+            Some(existing) if existing != withdrawer => Err(StakeError::DifferentWithdrawer {
+                validator: validator.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn add_stake(
+        &mut self,
+        validator: &str,
+        withdrawer: &str,
+        amount: Amount,
+    ) -> Result<(), StakeError> {
+        self.check_validator_withdrawer(validator, withdrawer)?;
+        self.withdrawer_by_validator
+            .insert(validator.to_string(), withdrawer.to_string());
+
+        let balance = self
+            .balances
+            .entry((validator.to_string(), withdrawer.to_string()))
+            .or_insert_with(|| Balance::new(0));
+        *balance = Balance::new(balance.get() + amount.get());
+        Ok(())
+    }
+
+    /// Removes `amount` from the validator/withdrawer pair's stake. Once
+    /// the pair's stake reaches zero, the validator's withdrawer binding is
+    /// cleared, freeing the validator to be staked under a new withdrawer.
+    pub fn remove_stake(
+        &mut self,
+        validator: &str,
+        withdrawer: &str,
+        amount: Amount,
+    ) -> Result<(), StakeError> {
+        let key = (validator.to_string(), withdrawer.to_string());
+        let balance = self.balances.get_mut(&key).ok_or_else(|| StakeError::UnknownPair {
+            validator: validator.to_string(),
+            withdrawer: withdrawer.to_string(),
+        })?;
+        if amount.get() > balance.get() {
+            return Err(StakeError::InsufficientStake {
+                needed: amount.get(),
+                available: balance.get(),
+            });
+        }
+        *balance = Balance::new(balance.get() - amount.get());
+
+        if balance.get() == 0 {
+            self.balances.remove(&key);
+            self.withdrawer_by_validator.remove(validator);
+        }
+        Ok(())
+    }
+
+    pub fn balance_of(&self, validator: &str, withdrawer: &str) -> Option<u64> {
+        self.balances
+            .get(&(validator.to_string(), withdrawer.to_string()))
+            .map(Balance::get)
+    }
+}
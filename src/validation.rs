@@ -0,0 +1,93 @@
+//! A small composable validation framework of reusable, testable rules, used
+//! by the `Amount` and `Balance` constructors. The `withdraw_funds_*`
+//! functions keep their own inline `if x > y` checks.
+
+/// Checks whether a value of type `T` is acceptable.
+pub trait Validator<T> {
+    fn check(&self, value: &T) -> Result<(), String>;
+}
+
+pub struct MinValue(pub u64);
+
+impl Validator<u64> for MinValue {
+    fn check(&self, value: &u64) -> Result<(), String> {
+        if *value < self.0 {
+            Err(format!("value {value} is below the minimum of {}", self.0))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct MaxValue(pub u64);
+
+impl Validator<u64> for MaxValue {
+    fn check(&self, value: &u64) -> Result<(), String> {
+        if *value > self.0 {
+            Err(format!("value {value} is above the maximum of {}", self.0))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct NonZero;
+
+impl Validator<u64> for NonZero {
+    fn check(&self, value: &u64) -> Result<(), String> {
+        if *value == 0 {
+            Err("value must not be zero".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct MultipleOf(pub u64);
+
+impl Validator<u64> for MultipleOf {
+    fn check(&self, value: &u64) -> Result<(), String> {
+        if self.0 == 0 {
+            return Err("MultipleOf(0) cannot be satisfied".to_string());
+        }
+        if *value % self.0 != 0 {
+            Err(format!("value {value} is not a multiple of {}", self.0))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Passes only if both `A` and `B` pass.
+pub struct And<A, B>(pub A, pub B);
+
+impl<T, A: Validator<T>, B: Validator<T>> Validator<T> for And<A, B> {
+    fn check(&self, value: &T) -> Result<(), String> {
+        self.0.check(value)?;
+        self.1.check(value)
+    }
+}
+
+/// Passes if either `A` or `B` passes.
+pub struct Or<A, B>(pub A, pub B);
+
+impl<T, A: Validator<T>, B: Validator<T>> Validator<T> for Or<A, B> {
+    fn check(&self, value: &T) -> Result<(), String> {
+        match self.0.check(value) {
+            Ok(()) => Ok(()),
+            Err(first) => self.1.check(value).map_err(|second| format!("{first}; {second}")),
+        }
+    }
+}
+
+/// Passes if `A` fails.
+pub struct Not<A>(pub A);
+
+impl<T, A: Validator<T>> Validator<T> for Not<A> {
+    fn check(&self, value: &T) -> Result<(), String> {
+        match self.0.check(value) {
+            Ok(()) => Err("value unexpectedly passed the negated validator".to_string()),
+            Err(_) => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Tallies voting power across a set of `Balance`-staked validators and
+//! decides whether a configurable trust threshold has been met.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::Balance;
+
+/// A `numerator / denominator` fraction of total voting power that must be
+/// tallied for a vote to be considered trusted.
+pub struct TrustThreshold {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct VotingPowerTally {
+    pub total: u64,
+    pub tallied: u64,
+}
+
+pub struct Vote {
+    pub validator_id: String,
+    pub valid: bool,
+}
+
+pub struct VotingPowerCalculator;
+
+impl VotingPowerCalculator {
+    /// Tallies the `Balance` of validators in `validator_set` with a
+    /// present, valid vote, and reports whether `threshold` was met. Tallying
+    /// stops at the first vote that meets the threshold, so `tallied` then
+    /// leaves out any later votes.
+    ///
+    /// Duplicate votes from the same validator are deduplicated, votes from
+    /// validators outside `validator_set` are ignored, and an empty
+    /// `validator_set` never meets the threshold.
+    pub fn voting_power_in(
+        &self,
+        votes: &[Vote],
+        validator_set: &HashMap<String, Balance>,
+        threshold: &TrustThreshold,
+    ) -> (VotingPowerTally, bool) {
+        let total: u64 = validator_set.values().map(Balance::get).sum();
+        let mut tallied = 0u64;
+        let mut seen = HashSet::new();
+        let mut threshold_met = false;
+
+        for vote in votes {
+            if !vote.valid || !seen.insert(vote.validator_id.as_str()) {
+                continue;
+            }
+            let Some(balance) = validator_set.get(&vote.validator_id) else {
+                continue;
+            };
+            tallied += balance.get();
+            if tallied * threshold.denominator > total * threshold.numerator {
+                threshold_met = true;
+                break;
+            }
+        }
+
+        (VotingPowerTally { total, tallied }, threshold_met)
+    }
+}
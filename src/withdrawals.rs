@@ -0,0 +1,88 @@
+//! Turns the single `withdraw_funds` helper into a per-epoch sweep over a
+//! whole validator set, capping each validator's contribution at a maximum
+//! effective balance and queuing the excess as a partial withdrawal.
+
+use crate::{Amount, Balance};
+
+/// The most of a validator's balance that counts towards its stake; any
+/// excess is swept out as a partial withdrawal instead.
+pub const MAX_EFFECTIVE_BALANCE: u64 = 32;
+
+/// A single validator's withdrawal-relevant state.
+pub struct Validator {
+    pub balance: Balance,
+    /// `true` once the validator has fully exited and its whole balance is
+    /// eligible to be withdrawn.
+    pub fully_withdrawable: bool,
+    /// Partial withdrawals already queued for this validator but not yet
+    /// swept, so their amounts aren't counted twice.
+    pub pending_partial_withdrawals: Vec<Amount>,
+}
+
+pub struct Withdrawal {
+    pub validator_index: usize,
+    pub amount: Amount,
+}
+
+/// Sweeps validators in round-robin order, starting at
+/// `next_withdrawal_validator_index`, to
+/// find the withdrawals due this epoch.
+pub struct WithdrawalsState {
+    pub validators: Vec<Validator>,
+    pub next_withdrawal_validator_index: usize,
+}
+
+/// A validator's stake never counts for more than `MAX_EFFECTIVE_BALANCE`.
+pub fn get_active_balance(validator: &Validator) -> u64 {
+    validator.balance.get().min(MAX_EFFECTIVE_BALANCE)
+}
+
+/// The amount already queued to be withdrawn from `validator` but not yet
+/// swept out of its balance.
+pub fn get_pending_balance_to_withdraw(validator: &Validator) -> u64 {
+    validator
+        .pending_partial_withdrawals
+        .iter()
+        .map(Amount::get)
+        .sum()
+}
+
+/// Sweeps at most `bound` validators starting at `state`'s stored cursor,
+/// emitting a full withdrawal for every fully-withdrawable validator and a
+/// partial withdrawal of any excess over `MAX_EFFECTIVE_BALANCE` that is not
+/// already covered by its pending partial withdrawals. Returns the
+/// withdrawals found and the cursor
+/// the next call should resume from.
+pub fn get_expected_withdrawals(state: &WithdrawalsState, bound: usize) -> (Vec<Withdrawal>, usize) {
+    let validators = &state.validators;
+    let len = validators.len();
+    if len == 0 {
+        return (Vec::new(), state.next_withdrawal_validator_index);
+    }
+
+    let mut withdrawals = Vec::new();
+    let mut cursor = state.next_withdrawal_validator_index % len;
+
+    for _ in 0..bound.min(len) {
+        let validator = &validators[cursor];
+        if validator.fully_withdrawable {
+            withdrawals.push(Withdrawal {
+                validator_index: cursor,
+                amount: Amount::new(validator.balance.get()),
+            });
+        } else {
+            let excess = validator.balance.get().saturating_sub(MAX_EFFECTIVE_BALANCE);
+            let already_pending = get_pending_balance_to_withdraw(validator);
+            let partial = excess.saturating_sub(already_pending);
+            if partial > 0 {
+                withdrawals.push(Withdrawal {
+                    validator_index: cursor,
+                    amount: Amount::new(partial),
+                });
+            }
+        }
+        cursor = (cursor + 1) % len;
+    }
+
+    (withdrawals, cursor)
+}